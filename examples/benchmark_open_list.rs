@@ -0,0 +1,170 @@
+//! Ad-hoc timing comparison between `execute_a_star`'s 4-ary open list ([`DAryHeap`], duplicated
+//! here since it lives in a binary-only crate with no `lib.rs` to depend on from an example) and a
+//! plain `std::collections::BinaryHeap`, confirming the d-ary heap is a speedup on a large,
+//! A*-shaped push/pop workload. Run with `cargo run --release --example benchmark_open_list` once
+//! this crate has a `Cargo.toml`.
+//!
+//! The workload replays `execute_a_star`'s access pattern on a large generated grid: each of the
+//! `GRID_SIZE * GRID_SIZE` expansions pushes `BRANCHING_FACTOR` (8-directional movement) freshly
+//! relaxed neighbours and pops the best one, with deterministic pseudo-random costs standing in for
+//! real tile costs so every run is comparable.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::time::Instant;
+
+const GRID_SIZE: u32 = 1000;
+const BRANCHING_FACTOR: u32 = 8;
+const ARITY: usize = 4;
+
+#[derive(Debug, Copy, Clone)]
+struct Entry {
+    cost: f32,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        // Reversed, same as `Visited`'s `Ord`, so both heaps behave as min-heaps by cost.
+        self.cost.partial_cmp(&other.cost).map(|cmp| cmp.reverse())
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Copy of `crate::d_ary_heap::DAryHeap`; duplicated rather than `mod`-included since this example
+/// has no library target to pull it in from.
+struct DAryHeap<T: Ord> {
+    items: Vec<T>,
+}
+
+impl<T: Ord> DAryHeap<T> {
+    fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    fn push(&mut self, item: T) {
+        self.items.push(item);
+        self.sift_up(self.items.len() - 1);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let last = self.items.len().checked_sub(1)?;
+        self.items.swap(0, last);
+        let popped = self.items.pop();
+
+        if !self.items.is_empty() {
+            self.sift_down(0);
+        }
+
+        popped
+    }
+
+    fn sift_up(&mut self, mut index: usize) {
+        while index > 0 {
+            let parent = (index - 1) / ARITY;
+
+            if self.items[index] <= self.items[parent] {
+                break;
+            }
+
+            self.items.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut index: usize) {
+        loop {
+            let first_child = ARITY * index + 1;
+
+            if first_child >= self.items.len() {
+                break;
+            }
+
+            let last_child = (first_child + ARITY).min(self.items.len());
+            let mut largest = index;
+
+            for child in first_child..last_child {
+                if self.items[child] > self.items[largest] {
+                    largest = child;
+                }
+            }
+
+            if largest == index {
+                break;
+            }
+
+            self.items.swap(index, largest);
+            index = largest;
+        }
+    }
+}
+
+/// Deterministic xorshift PRNG so both heaps see the identical sequence of costs.
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn next_cost(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        (self.0 % 10_000) as f32 / 100.
+    }
+}
+
+fn bench_binary_heap(steps: u32) -> u128 {
+    let mut rng = Xorshift(0x9E3779B97F4A7C15);
+    let mut heap: BinaryHeap<Entry> = BinaryHeap::new();
+
+    let start = Instant::now();
+
+    for _ in 0..steps {
+        for _ in 0..BRANCHING_FACTOR {
+            heap.push(Entry { cost: rng.next_cost() });
+        }
+
+        heap.pop();
+    }
+
+    start.elapsed().as_micros()
+}
+
+fn bench_d_ary_heap(steps: u32) -> u128 {
+    let mut rng = Xorshift(0x9E3779B97F4A7C15);
+    let mut heap: DAryHeap<Entry> = DAryHeap::new();
+
+    let start = Instant::now();
+
+    for _ in 0..steps {
+        for _ in 0..BRANCHING_FACTOR {
+            heap.push(Entry { cost: rng.next_cost() });
+        }
+
+        heap.pop();
+    }
+
+    start.elapsed().as_micros()
+}
+
+fn main() {
+    let steps = GRID_SIZE * GRID_SIZE;
+
+    let binary_micros = bench_binary_heap(steps);
+    let d_ary_micros = bench_d_ary_heap(steps);
+
+    println!("BinaryHeap (arity 2): {} steps in {} us", steps, binary_micros);
+    println!("DAryHeap (arity {}): {} steps in {} us", ARITY, steps, d_ary_micros);
+    println!("Speedup: {:.2}x", binary_micros as f64 / d_ary_micros as f64);
+}
@@ -0,0 +1,64 @@
+use std::cmp::Ordering;
+use std::hash::Hash;
+
+/// A weighted graph that [`crate::pathfinding::execute_a_star`] can search, independent of any
+/// particular representation. `GridGraph` is the original implementation; anything else that can
+/// enumerate a node's neighbours and cost an edge between two nodes can be searched the same way.
+pub trait Graph {
+    type Node: Copy + Eq + Hash + std::fmt::Debug;
+
+    /// Nodes directly reachable from `node`.
+    fn neighbours(&self, node: &Self::Node) -> impl Iterator<Item = Self::Node>;
+
+    /// Cost of moving from `from` into `to`. Graphs whose cost only depends on the destination
+    /// (e.g. a grid's per-tile cost) are free to ignore `from`.
+    fn cost(&self, from: &Self::Node, to: &Self::Node) -> f32;
+}
+
+/// A node that has been placed on the open/closed list together with its accumulated cost.
+///
+/// Both `execute_a_star` and `execute_a_star_constrained` use the same lazy-deletion scheme: a
+/// cheaper relaxation of a node already on the open list is pushed as a *new* entry rather than
+/// updating the old one in place (the open list can't do that efficiently), and the stale entry is
+/// just skipped when it's eventually popped, by comparing its cost against the best score recorded
+/// for that node since.
+#[derive(Debug, Copy, Clone)]
+pub struct Visited<N> {
+    pub node: N,
+    pub cost: f32,
+}
+
+impl<N> Visited<N> {
+    pub fn new(node: N, cost: f32) -> Self {
+        Self {
+            node,
+            cost,
+        }
+    }
+}
+
+impl<N: PartialEq> PartialEq for Visited<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node && self.cost == other.cost
+    }
+}
+
+impl<N: PartialEq> PartialEq<N> for Visited<N> {
+    fn eq(&self, other: &N) -> bool {
+        &self.node == other
+    }
+}
+
+impl<N: Eq> Eq for Visited<N> {}
+
+impl<N: Eq> PartialOrd for Visited<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.cost.partial_cmp(&other.cost).map(|cmp| cmp.reverse())
+    }
+}
+
+impl<N: Eq> Ord for Visited<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+    }
+}
@@ -1,38 +1,104 @@
-use std::cmp::Ordering;
-use std::hash::{Hash, Hasher};
+use crate::graph::{Graph, Visited};
 
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
 pub struct Direction {
     x: i32,
     y: i32,
 }
 
+impl Direction {
+    /// `true` if this direction moves along both axes at once.
+    pub fn is_diagonal(&self) -> bool {
+        self.x != 0 && self.y != 0
+    }
+
+    /// Factor the cost of a tile entered via this direction should be multiplied by,
+    /// so diagonal steps (which cover √2 tile-widths) are properly costlier than orthogonal ones.
+    pub fn cost_multiplier(&self) -> f32 {
+        if self.is_diagonal() {
+            std::f32::consts::SQRT_2
+        } else {
+            1.
+        }
+    }
+
+    /// `true` if `other` points in exactly the same direction as `self`.
+    pub fn is_same(&self, other: &Direction) -> bool {
+        self.x == other.x && self.y == other.y
+    }
+
+    /// `true` if `other` points exactly opposite to `self`, i.e. would reverse a move.
+    pub fn is_opposite(&self, other: &Direction) -> bool {
+        self.x == -other.x && self.y == -other.y
+    }
+}
+
 pub const DIRECTION_UP: Direction = Direction { x: 0, y: -1 };
 pub const DIRECTION_DOWN: Direction = Direction { x: 0, y: 1 };
 pub const DIRECTION_LEFT: Direction = Direction { x: -1, y: 0 };
 pub const DIRECTION_RIGHT: Direction = Direction { x: 1, y: 0 };
 
-pub const ALL_DIRECTIONS: [Direction; 4] = [DIRECTION_UP, DIRECTION_DOWN, DIRECTION_LEFT, DIRECTION_RIGHT];
+pub const DIRECTION_UP_LEFT: Direction = Direction { x: -1, y: -1 };
+pub const DIRECTION_UP_RIGHT: Direction = Direction { x: 1, y: -1 };
+pub const DIRECTION_DOWN_LEFT: Direction = Direction { x: -1, y: 1 };
+pub const DIRECTION_DOWN_RIGHT: Direction = Direction { x: 1, y: 1 };
+
+pub const CARDINAL_DIRECTIONS: [Direction; 4] = [DIRECTION_UP, DIRECTION_DOWN, DIRECTION_LEFT, DIRECTION_RIGHT];
+pub const DIAGONAL_DIRECTIONS: [Direction; 4] = [DIRECTION_UP_LEFT, DIRECTION_UP_RIGHT, DIRECTION_DOWN_LEFT, DIRECTION_DOWN_RIGHT];
+
+/// All 8 directions, cardinal moves first so index 0..4 matches [`CARDINAL_DIRECTIONS`].
+pub const ALL_DIRECTIONS: [Direction; 8] = [
+    DIRECTION_UP, DIRECTION_DOWN, DIRECTION_LEFT, DIRECTION_RIGHT,
+    DIRECTION_UP_LEFT, DIRECTION_UP_RIGHT, DIRECTION_DOWN_LEFT, DIRECTION_DOWN_RIGHT,
+];
 
 pub struct GridGraph<'a> {
     pub width: u32,
     pub height: u32,
+    /// Per-tile move cost. `0` is a reserved sentinel meaning "wall" (impassable) everywhere in
+    /// this crate; every other value is that tile's traversal cost.
     pub tiles: &'a [&'a [u8]],
+    directions: &'a [Direction],
+    origin_x: u32,
+    origin_y: u32,
 }
 
 impl<'a> GridGraph<'a> {
-    pub fn new(width: u32, height: u32, tiles: &'a[&'a[u8]]) -> Self {
+    pub fn new(width: u32, height: u32, tiles: &'a [&'a [u8]], directions: &'a [Direction]) -> Self {
         Self {
             width,
             height,
             tiles,
+            directions,
+            origin_x: 0,
+            origin_y: 0,
         }
     }
 
-    pub fn get_neighbouring_vertex(&self, vertex: &VisitedGraphVertex, direction: Direction) -> Option<GraphVertex> {
+    /// A view restricted to the `width`x`height` rectangle starting at `(origin_x, origin_y)`,
+    /// sharing the same underlying tiles and connectivity. Used to confine a search to a single
+    /// chunk when precomputing intra-chunk costs for hierarchical pathfinding.
+    pub fn sub_view(&self, origin_x: u32, origin_y: u32, width: u32, height: u32) -> GridGraph<'a> {
+        Self {
+            width,
+            height,
+            tiles: self.tiles,
+            directions: self.directions,
+            origin_x: self.origin_x + origin_x,
+            origin_y: self.origin_y + origin_y,
+        }
+    }
+
+    pub fn directions(&self) -> &'a [Direction] {
+        self.directions
+    }
+
+    pub fn get_neighbouring_vertex(&self, vertex: &GraphVertex, direction: &Direction) -> Option<GraphVertex> {
         let neighbour_x = vertex.x as i32 + direction.x;
         let neighbour_y = vertex.y as i32 + direction.y;
 
-        if neighbour_x < 0 || neighbour_y < 0 || neighbour_x >= self.width as i32 || neighbour_y >= self.height as i32 {
+        if neighbour_x < self.origin_x as i32 || neighbour_y < self.origin_y as i32
+            || neighbour_x >= (self.origin_x + self.width) as i32 || neighbour_y >= (self.origin_y + self.height) as i32 {
             return None;
         }
 
@@ -45,79 +111,47 @@ impl<'a> GridGraph<'a> {
     pub fn get_cost(&self, vertex: &GraphVertex) -> u8 {
         self.tiles[vertex.x as usize][vertex.y as usize]
     }
-}
 
-#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
-pub struct GraphVertex {
-    pub x: u32,
-    pub y: u32,
+    /// `false` for a tile with cost `0`, the crate-wide "wall" sentinel. Neither [`Graph::neighbours`]
+    /// nor `execute_a_star_constrained` will ever step onto such a tile.
+    pub fn is_walkable(&self, vertex: &GraphVertex) -> bool {
+        self.get_cost(vertex) > 0
+    }
 }
 
-impl GraphVertex {
-    pub fn new(x: u32, y: u32) -> Self {
-        Self {
-            x,
-            y,
-        }
-    }
+impl<'a> Graph for GridGraph<'a> {
+    type Node = GraphVertex;
 
-    pub fn into_visited(self, visit_cost: f32) -> VisitedGraphVertex {
-        VisitedGraphVertex::new(self.x, self.y, visit_cost)
+    fn neighbours(&self, node: &GraphVertex) -> impl Iterator<Item = GraphVertex> {
+        self.directions.iter()
+            .filter_map(move |direction| self.get_neighbouring_vertex(node, direction))
+            .filter(move |neighbour| self.is_walkable(neighbour))
     }
-}
 
-impl From<VisitedGraphVertex> for GraphVertex {
-    fn from(vertex: VisitedGraphVertex) -> Self {
-        GraphVertex::new(vertex.x, vertex.y)
+    fn cost(&self, from: &GraphVertex, to: &GraphVertex) -> f32 {
+        let dx = to.x as i32 - from.x as i32;
+        let dy = to.y as i32 - from.y as i32;
+        let is_diagonal = dx != 0 && dy != 0;
+
+        self.get_cost(to) as f32 * if is_diagonal { std::f32::consts::SQRT_2 } else { 1. }
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-pub struct VisitedGraphVertex {
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct GraphVertex {
     pub x: u32,
     pub y: u32,
-    pub cost: f32,
 }
 
-impl VisitedGraphVertex {
-    pub fn new(x: u32, y: u32, cost: f32) -> Self {
+impl GraphVertex {
+    pub fn new(x: u32, y: u32) -> Self {
         Self {
             x,
             y,
-            cost,
         }
     }
-}
-
-impl PartialEq for VisitedGraphVertex {
-    fn eq(&self, other: &Self) -> bool {
-        (self.x, self.y, self.cost) == (other.x, other.y, other.cost)
-    }
-}
-
-impl PartialEq<GraphVertex> for VisitedGraphVertex {
-    fn eq(&self, other: &GraphVertex) -> bool {
-        self.x == other.x && self.y == other.y
-    }
-}
-
-impl Eq for VisitedGraphVertex {}
-
-impl PartialOrd for VisitedGraphVertex {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.cost.partial_cmp(&other.cost).map(|cmp| cmp.reverse())
-    }
-}
-
-impl Ord for VisitedGraphVertex {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap_or(Ordering::Equal)
-    }
-}
 
-impl Hash for VisitedGraphVertex {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.x.hash(state);
-        self.y.hash(state);
+    pub fn into_visited(self, visit_cost: f32) -> Visited<GraphVertex> {
+        Visited::new(self, visit_cost)
     }
 }
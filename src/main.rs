@@ -18,20 +18,92 @@ pub struct Args {
 
     #[clap(value_enum, short = 'f', long)]
     pub cost_function_enum: CostFunction,
+
+    /// Grid connectivity: 4 for cardinal moves only, 8 to also allow diagonals
+    #[clap(value_enum, long, default_value = "4")]
+    pub connectivity: Connectivity,
+
+    /// Enable "crucible" mode: the path may take at most `--max-run` consecutive steps in the
+    /// same direction and must take at least `--min-run` steps before it is allowed to turn.
+    /// Conflicts with `--hierarchical`, which only ever refines with the unconstrained search
+    /// and would otherwise silently drop the run-length constraint
+    #[clap(long, conflicts_with = "hierarchical")]
+    pub crucible: bool,
+
+    /// Minimum number of consecutive steps in a direction before a turn (or the goal) is allowed, in crucible mode
+    #[clap(long, default_value_t = 1)]
+    pub min_run: u32,
+
+    /// Maximum number of consecutive steps allowed in the same direction, in crucible mode
+    #[clap(long, default_value_t = u32::MAX)]
+    pub max_run: u32,
+
+    /// Use hierarchical pathfinding: partition the grid into chunks and route through a
+    /// precomputed abstract graph instead of searching every tile, for near-instant approximate
+    /// paths on large grids
+    #[clap(long)]
+    pub hierarchical: bool,
+
+    /// Chunk size used to build the abstract graph in hierarchical mode
+    #[clap(long, default_value_t = path_cache::DEFAULT_CHUNK_SIZE)]
+    pub chunk_size: u32,
+
+    /// Bound the open list to this many lowest-f-cost entries (beam search), trading optimality
+    /// for flat memory usage on huge grids. Unset behaves identically to exact A*
+    #[clap(long)]
+    pub beam_width: Option<usize>,
+
+    /// Input format: an image with colored start/goal/cost pixels, or a plain-text whitespace-
+    /// separated adjacency/cost matrix with `S`/`G` marking the start/goal vertex
+    #[clap(value_enum, long, default_value = "image")]
+    pub input_format: InputFormat,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum InputFormat {
+    Image,
+    Text,
 }
 
 pub struct PathfindingOptions {
     pub cost_weight: f32,
     pub heuristics_weight: f32,
+    pub min_run: u32,
+    pub max_run: u32,
+    pub beam_width: Option<usize>,
 }
 
 use image::{GenericImageView, Rgba};
 
-use crate::grid_graph::{GraphVertex, GridGraph, VisitedGraphVertex};
-use crate::pathfinding::execute_a_star;
+use crate::graph::Visited;
+use crate::grid_graph::{ALL_DIRECTIONS, CARDINAL_DIRECTIONS, Direction, GraphVertex, GridGraph};
+use crate::path_cache::PathCache;
+use crate::pathfinding::{execute_a_star, execute_a_star_constrained};
+use crate::text_graph::TextGraph;
 
+mod d_ary_heap;
+mod graph;
 mod pathfinding;
 mod grid_graph;
+mod path_cache;
+mod text_graph;
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum Connectivity {
+    #[clap(name = "4")]
+    Four,
+    #[clap(name = "8")]
+    Eight,
+}
+
+impl Connectivity {
+    fn directions(&self) -> &'static [Direction] {
+        match self {
+            Connectivity::Four => &CARDINAL_DIRECTIONS,
+            Connectivity::Eight => &ALL_DIRECTIONS,
+        }
+    }
+}
 
 const EMPTY_VERTEX_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
 
@@ -42,44 +114,79 @@ const PATH_VERTEX_COLOR: Rgba<u8> = Rgba([255, 128, 0, 255]);
 const VISITED_VERTEX_COLOR: Rgba<u8> = Rgba([64, 64, 64, 255]);
 
 
-fn zero_cost_function(start_vertex: &GraphVertex, goal_vertex: &GraphVertex, last_visited_vertex: &VisitedGraphVertex, current_vertex: &GraphVertex, current_vertex_cost: u8, options: &PathfindingOptions) -> f32 {
-    (last_visited_vertex.cost + current_vertex_cost as f32) * options.cost_weight
+fn zero_cost_function(start_vertex: &GraphVertex, goal_vertex: &GraphVertex, last_visited_vertex: &Visited<GraphVertex>, current_vertex: &GraphVertex, current_vertex_cost: f32, options: &PathfindingOptions) -> f32 {
+    (last_visited_vertex.cost + current_vertex_cost) * options.cost_weight
 }
 
-fn euclidean_distance_cost_function(start_vertex: &GraphVertex, goal_vertex: &GraphVertex, last_visited_vertex: &VisitedGraphVertex, current_vertex: &GraphVertex, current_vertex_cost: u8, options: &PathfindingOptions) -> f32 {
-    let g = last_visited_vertex.cost + current_vertex_cost as f32;
+fn euclidean_distance_cost_function(start_vertex: &GraphVertex, goal_vertex: &GraphVertex, last_visited_vertex: &Visited<GraphVertex>, current_vertex: &GraphVertex, current_vertex_cost: f32, options: &PathfindingOptions) -> f32 {
+    let g = last_visited_vertex.cost + current_vertex_cost;
     let h = ((goal_vertex.x as f32 - current_vertex.x as f32).abs().powf(2.) + (goal_vertex.y as f32 - current_vertex.y as f32).abs().powf(2.)).sqrt();
 
     g * options.cost_weight + h * options.heuristics_weight
 }
 
-fn manhattan_distance_cost_function(start_vertex: &GraphVertex, goal_vertex: &GraphVertex, last_visited_vertex: &VisitedGraphVertex, current_vertex: &GraphVertex, current_vertex_cost: u8, options: &PathfindingOptions) -> f32 {
-    let g = last_visited_vertex.cost + current_vertex_cost as f32;
+fn manhattan_distance_cost_function(start_vertex: &GraphVertex, goal_vertex: &GraphVertex, last_visited_vertex: &Visited<GraphVertex>, current_vertex: &GraphVertex, current_vertex_cost: f32, options: &PathfindingOptions) -> f32 {
+    let g = last_visited_vertex.cost + current_vertex_cost;
     let h = (current_vertex.x as f32 - goal_vertex.x as f32).abs() + (current_vertex.y as f32 - goal_vertex.y as f32).abs();
 
     g * options.cost_weight + h * options.heuristics_weight
 }
 
+/// Admissible heuristic for 8-directional grid movement: `(dx + dy) + (√2 − 2)·min(dx, dy)`.
+/// Equal to `max(dx, dy) + (√2 − 1)·min(dx, dy)`: the exact distance on an open grid of unit-cost
+/// tiles where a diagonal step costs √2 times an orthogonal one.
+fn octile_distance_cost_function(start_vertex: &GraphVertex, goal_vertex: &GraphVertex, last_visited_vertex: &Visited<GraphVertex>, current_vertex: &GraphVertex, current_vertex_cost: f32, options: &PathfindingOptions) -> f32 {
+    let g = last_visited_vertex.cost + current_vertex_cost;
+    let dx = (current_vertex.x as f32 - goal_vertex.x as f32).abs();
+    let dy = (current_vertex.y as f32 - goal_vertex.y as f32).abs();
+    let h = (dx + dy) + (2_f32.sqrt() - 2.) * dx.min(dy);
+
+    g * options.cost_weight + h * options.heuristics_weight
+}
+
+/// Admissible heuristic for 8-directional grid movement when diagonal steps cost the same as
+/// orthogonal ones: `h = max(dx, dy)`.
+fn chebyshev_distance_cost_function(start_vertex: &GraphVertex, goal_vertex: &GraphVertex, last_visited_vertex: &Visited<GraphVertex>, current_vertex: &GraphVertex, current_vertex_cost: f32, options: &PathfindingOptions) -> f32 {
+    let g = last_visited_vertex.cost + current_vertex_cost;
+    let dx = (current_vertex.x as f32 - goal_vertex.x as f32).abs();
+    let dy = (current_vertex.y as f32 - goal_vertex.y as f32).abs();
+    let h = dx.max(dy);
+
+    g * options.cost_weight + h * options.heuristics_weight
+}
+
 #[derive(clap::ValueEnum, Clone, Debug)]
 pub enum CostFunction {
     ZeroCost,
     EuclideanDistance,
     ManhattanDistance,
+    OctileDistance,
+    ChebyshevDistance,
 }
 
 impl CostFunction {
-    fn get_cost_function(&self) -> &dyn Fn(&GraphVertex, &GraphVertex, &VisitedGraphVertex, &GraphVertex, u8, &PathfindingOptions) -> f32 {
+    fn get_cost_function(&self) -> &dyn Fn(&GraphVertex, &GraphVertex, &Visited<GraphVertex>, &GraphVertex, f32, &PathfindingOptions) -> f32 {
         match self {
             CostFunction::ZeroCost => &zero_cost_function,
             CostFunction::EuclideanDistance => &euclidean_distance_cost_function,
-            CostFunction::ManhattanDistance => &manhattan_distance_cost_function
+            CostFunction::ManhattanDistance => &manhattan_distance_cost_function,
+            CostFunction::OctileDistance => &octile_distance_cost_function,
+            CostFunction::ChebyshevDistance => &chebyshev_distance_cost_function,
         }
     }
 }
 
 fn main() {
     let args: Args = Args::parse();
-    let input_image = image::open(args.input_path).expect("File not found!");
+
+    match args.input_format {
+        InputFormat::Image => run_on_image(args),
+        InputFormat::Text => run_on_text_graph(args),
+    }
+}
+
+fn run_on_image(args: Args) {
+    let input_image = image::open(&args.input_path).expect("File not found!");
 
     let (grid_width, grid_height) = input_image.dimensions();
     let mut tiles_grid_raw = vec![0_u8; (grid_width * grid_height) as _];
@@ -109,12 +216,28 @@ fn main() {
     let goal_vertex = goal_vertex.expect("No goal vertex with color rgba(255, 0, 0, 255) found!");
 
     let tiles_grid_base: Vec<_> = tiles_grid_raw.as_slice().chunks(grid_width as _).collect();
-    let grid_map = GridGraph::new(grid_width, grid_height, tiles_grid_base.as_slice());
+    let grid_map = GridGraph::new(grid_width, grid_height, tiles_grid_base.as_slice(), args.connectivity.directions());
 
-    let path_result = execute_a_star(&grid_map, start_vertex, goal_vertex, cost_function, &PathfindingOptions {
+    let pathfinding_options = PathfindingOptions {
         cost_weight: args.cost_weight,
         heuristics_weight: args.heuristics_weight,
-    }).expect("Couldn't find valid path");
+        min_run: args.min_run,
+        max_run: args.max_run,
+        beam_width: args.beam_width,
+    };
+
+    let path_result = if args.hierarchical {
+        let path_cache = PathCache::build(&grid_map, args.chunk_size, cost_function, &pathfinding_options);
+        let (path_result, avoided_vertices) = path_cache.find_path(&grid_map, start_vertex, goal_vertex, cost_function, &pathfinding_options).expect("Couldn't find valid path");
+
+        println!("Hierarchical search avoided an estimated {} low-level vertices", avoided_vertices);
+
+        path_result
+    } else if args.crucible {
+        execute_a_star_constrained(&grid_map, start_vertex, goal_vertex, cost_function, &pathfinding_options).expect("Couldn't find valid path")
+    } else {
+        execute_a_star(&grid_map, start_vertex, goal_vertex, cost_function, &pathfinding_options).expect("Couldn't find valid path")
+    };
 
     println!("Found path: {:?}, visited {} vertices", path_result.path, path_result.visited_vertices.len());
 
@@ -133,7 +256,7 @@ fn main() {
 
     for visited_vertex in &path_result.visited_vertices {
         let cost_color = ((visited_vertex.cost / max_visited_cost) * 200.).round() as u8;
-        result_img[(visited_vertex.x, visited_vertex.y)] = Rgba([cost_color, cost_color, cost_color, 255]);
+        result_img[(visited_vertex.node.x, visited_vertex.node.y)] = Rgba([cost_color, cost_color, cost_color, 255]);
     }
 
     let mut max_cost = 0_f32;
@@ -144,11 +267,40 @@ fn main() {
     }
 
     for path_vertex in &path_result.path {
-        result_img[(path_vertex.x, path_vertex.y)] = Rgba([((path_vertex.cost / max_cost) * 255.).round() as u8, 128, 0, 255]);
+        result_img[(path_vertex.node.x, path_vertex.node.y)] = Rgba([((path_vertex.cost / max_cost) * 255.).round() as u8, 128, 0, 255]);
     }
 
     result_img[(start_vertex.x, start_vertex.y)] = START_VERTEX_COLOR;
     result_img[(goal_vertex.x, goal_vertex.y)] = GOAL_VERTEX_COLOR;
 
     result_img.save("path_result.png").expect("Couldn't save resulting path image");
+}
+
+/// Runs the plain A* search over a [`TextGraph`] parsed from `args.input_path` and prints the
+/// resulting path. Crucible and hierarchical mode are `GridGraph`-specific (they rely on its fixed
+/// `Direction`s and chunk layout) and aren't available here; only `--beam-width` still applies.
+fn run_on_text_graph(args: Args) {
+    assert!(matches!(args.cost_function_enum, CostFunction::ZeroCost),
+        "--cost-function-enum {:?} is a spatial heuristic over GraphVertex.x/y; it's meaningless \
+         for --input-format text, whose GraphVertex.x is just a vertex index. Use zero-cost.",
+        args.cost_function_enum);
+
+    let input = std::fs::read_to_string(&args.input_path).expect("File not found!");
+    let (text_graph, start_vertex, goal_vertex) = TextGraph::parse(&input);
+
+    println!("Parsed a text graph with {} vertices", text_graph.vertex_count());
+
+    let cost_function = args.cost_function_enum.get_cost_function();
+
+    let pathfinding_options = PathfindingOptions {
+        cost_weight: args.cost_weight,
+        heuristics_weight: args.heuristics_weight,
+        min_run: args.min_run,
+        max_run: args.max_run,
+        beam_width: args.beam_width,
+    };
+
+    let path_result = execute_a_star(&text_graph, start_vertex, goal_vertex, cost_function, &pathfinding_options).expect("Couldn't find valid path");
+
+    println!("Found path: {:?}, visited {} vertices", path_result.path, path_result.visited_vertices.len());
 }
\ No newline at end of file
@@ -0,0 +1,440 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::graph::Visited;
+use crate::grid_graph::{GraphVertex, GridGraph};
+use crate::pathfinding::{execute_a_star, PathResult};
+use crate::PathfindingOptions;
+
+pub const DEFAULT_CHUNK_SIZE: u32 = 16;
+
+/// An "entrance" vertex on a chunk boundary, usable as a node in the abstract chunk-to-chunk graph.
+#[derive(Debug, Copy, Clone)]
+pub struct AbstractNode {
+    pub vertex: GraphVertex,
+    pub chunk_x: u32,
+    pub chunk_y: u32,
+}
+
+/// A precomputed local path between two entrances of the same chunk.
+#[derive(Debug, Copy, Clone)]
+pub struct AbstractEdge {
+    pub from: usize,
+    pub to: usize,
+    pub cost: f32,
+}
+
+/// Abstract, chunk-level graph laid on top of a [`GridGraph`]: entrances are the nodes where
+/// walkable tiles straddle a chunk boundary, and edges are grid paths precomputed once between
+/// every pair of entrances belonging to the same chunk. Querying this small graph instead of the
+/// full grid gives near-instant approximate long-distance paths on large grids.
+#[derive(Debug)]
+pub struct PathCache {
+    pub width: u32,
+    pub height: u32,
+    pub chunk_size: u32,
+    pub chunks_x: u32,
+    pub chunks_y: u32,
+    pub nodes: Vec<AbstractNode>,
+    pub edges: Vec<AbstractEdge>,
+}
+
+struct AbstractVisit {
+    node: usize,
+    cost: f32,
+}
+
+impl PartialEq for AbstractVisit {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for AbstractVisit {}
+
+impl PartialOrd for AbstractVisit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.cost.partial_cmp(&other.cost).map(|cmp| cmp.reverse())
+    }
+}
+
+impl Ord for AbstractVisit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+    }
+}
+
+fn euclidean_heuristic(from: &GraphVertex, to: &GraphVertex) -> f32 {
+    ((from.x as f32 - to.x as f32).powf(2.) + (from.y as f32 - to.y as f32).powf(2.)).sqrt()
+}
+
+fn chunk_bounds(chunk_size: u32, graph_width: u32, graph_height: u32, chunk_x: u32, chunk_y: u32) -> (u32, u32, u32, u32) {
+    let origin_x = chunk_x * chunk_size;
+    let origin_y = chunk_y * chunk_size;
+    let width = chunk_size.min(graph_width - origin_x);
+    let height = chunk_size.min(graph_height - origin_y);
+
+    (origin_x, origin_y, width, height)
+}
+
+/// Bounding rectangle covering both `chunk_a` and `chunk_b` (the same single chunk if they're
+/// equal), used to confine a refine search to just the chunk(s) an abstract-path segment crosses.
+fn chunks_bounds(chunk_size: u32, graph_width: u32, graph_height: u32, chunk_a: (u32, u32), chunk_b: (u32, u32)) -> (u32, u32, u32, u32) {
+    let chunk_x_min = chunk_a.0.min(chunk_b.0);
+    let chunk_y_min = chunk_a.1.min(chunk_b.1);
+    let chunk_x_max = chunk_a.0.max(chunk_b.0);
+    let chunk_y_max = chunk_a.1.max(chunk_b.1);
+
+    let origin_x = chunk_x_min * chunk_size;
+    let origin_y = chunk_y_min * chunk_size;
+    let width = ((chunk_x_max + 1) * chunk_size).min(graph_width) - origin_x;
+    let height = ((chunk_y_max + 1) * chunk_size).min(graph_height) - origin_y;
+
+    (origin_x, origin_y, width, height)
+}
+
+impl PathCache {
+    /// Partitions `graph` into `chunk_size`x`chunk_size` chunks, finds the entrances where
+    /// walkable tiles straddle a chunk boundary, and runs the regular grid A* locally to cost
+    /// every pair of entrances within the same chunk.
+    pub fn build(graph: &GridGraph, chunk_size: u32, cost_func: &dyn Fn(&GraphVertex, &GraphVertex, &Visited<GraphVertex>, &GraphVertex, f32, &PathfindingOptions) -> f32, options: &PathfindingOptions) -> Self {
+        let chunks_x = (graph.width + chunk_size - 1) / chunk_size;
+        let chunks_y = (graph.height + chunk_size - 1) / chunk_size;
+
+        let mut nodes: Vec<AbstractNode> = Vec::new();
+        let mut edges: Vec<AbstractEdge> = Vec::new();
+
+        // Vertical chunk boundaries: entrances between horizontally-adjacent chunks
+        for chunk_x in 0..chunks_x.saturating_sub(1) {
+            let left_x = (chunk_x + 1) * chunk_size - 1;
+            let right_x = left_x + 1;
+
+            if right_x >= graph.width {
+                continue;
+            }
+
+            Self::scan_boundary(graph, chunk_size, left_x, right_x, true, &mut nodes, &mut edges);
+        }
+
+        // Horizontal chunk boundaries: entrances between vertically-adjacent chunks
+        for chunk_y in 0..chunks_y.saturating_sub(1) {
+            let top_y = (chunk_y + 1) * chunk_size - 1;
+            let bottom_y = top_y + 1;
+
+            if bottom_y >= graph.height {
+                continue;
+            }
+
+            Self::scan_boundary(graph, chunk_size, top_y, bottom_y, false, &mut nodes, &mut edges);
+        }
+
+        let mut cache = Self {
+            width: graph.width,
+            height: graph.height,
+            chunk_size,
+            chunks_x,
+            chunks_y,
+            nodes,
+            edges,
+        };
+
+        cache.link_intra_chunk_entrances(graph, cost_func, options);
+        cache
+    }
+
+    /// Scans one boundary line (vertical if `vertical`, else horizontal) for contiguous runs of
+    /// walkable tile pairs and emits entrance node pairs (and their crossing edges) for each run,
+    /// one pair per chunk-row/column the run spans -- a run can't be represented by a single
+    /// midpoint entrance once it's long enough to pass through more than one chunk.
+    fn scan_boundary(graph: &GridGraph, chunk_size: u32, near_line: u32, far_line: u32, vertical: bool, nodes: &mut Vec<AbstractNode>, edges: &mut Vec<AbstractEdge>) {
+        let line_len = if vertical { graph.height } else { graph.width };
+
+        let mut run_start: Option<u32> = None;
+
+        for pos in 0..=line_len {
+            let walkable = pos < line_len && {
+                let (near_vertex, far_vertex) = if vertical {
+                    (GraphVertex::new(near_line, pos), GraphVertex::new(far_line, pos))
+                } else {
+                    (GraphVertex::new(pos, near_line), GraphVertex::new(pos, far_line))
+                };
+
+                graph.is_walkable(&near_vertex) && graph.is_walkable(&far_vertex)
+            };
+
+            match (walkable, run_start) {
+                (true, None) => run_start = Some(pos),
+                (false, Some(start)) => {
+                    Self::emit_run_entrances(graph, chunk_size, near_line, far_line, vertical, start, pos - 1, nodes, edges);
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Emits one entrance node pair (and its crossing edge) per chunk-row/column the walkable run
+    /// `[run_start, run_end]` (inclusive, along the boundary line) passes through, at the midpoint
+    /// of the run's overlap with that chunk.
+    fn emit_run_entrances(graph: &GridGraph, chunk_size: u32, near_line: u32, far_line: u32, vertical: bool, run_start: u32, run_end: u32, nodes: &mut Vec<AbstractNode>, edges: &mut Vec<AbstractEdge>) {
+        let first_chunk = run_start / chunk_size;
+        let last_chunk = run_end / chunk_size;
+
+        for chunk in first_chunk..=last_chunk {
+            let chunk_lo = (chunk * chunk_size).max(run_start);
+            let chunk_hi = ((chunk + 1) * chunk_size - 1).min(run_end);
+            let mid = chunk_lo + (chunk_hi - chunk_lo) / 2;
+
+            let (near_vertex, far_vertex) = if vertical {
+                (GraphVertex::new(near_line, mid), GraphVertex::new(far_line, mid))
+            } else {
+                (GraphVertex::new(mid, near_line), GraphVertex::new(mid, far_line))
+            };
+
+            let near_idx = nodes.len();
+            nodes.push(AbstractNode { vertex: near_vertex, chunk_x: near_vertex.x / chunk_size, chunk_y: near_vertex.y / chunk_size });
+            let far_idx = nodes.len();
+            nodes.push(AbstractNode { vertex: far_vertex, chunk_x: far_vertex.x / chunk_size, chunk_y: far_vertex.y / chunk_size });
+
+            let crossing_cost = graph.get_cost(&far_vertex) as f32;
+            edges.push(AbstractEdge { from: near_idx, to: far_idx, cost: crossing_cost });
+            edges.push(AbstractEdge { from: far_idx, to: near_idx, cost: crossing_cost });
+        }
+    }
+
+    /// Runs the regular grid A* between every pair of entrances in each chunk and records the
+    /// resulting path cost as an abstract edge, so the abstract graph alone can be queried later.
+    fn link_intra_chunk_entrances(&mut self, graph: &GridGraph, cost_func: &dyn Fn(&GraphVertex, &GraphVertex, &Visited<GraphVertex>, &GraphVertex, f32, &PathfindingOptions) -> f32, options: &PathfindingOptions) {
+        for chunk_y in 0..self.chunks_y {
+            for chunk_x in 0..self.chunks_x {
+                let members: Vec<usize> = self.nodes.iter().enumerate()
+                    .filter(|(_, node)| node.chunk_x == chunk_x && node.chunk_y == chunk_y)
+                    .map(|(idx, _)| idx)
+                    .collect();
+
+                if members.len() < 2 {
+                    continue;
+                }
+
+                let (origin_x, origin_y, width, height) = chunk_bounds(self.chunk_size, self.width, self.height, chunk_x, chunk_y);
+                let chunk_view = graph.sub_view(origin_x, origin_y, width, height);
+
+                for i in 0..members.len() {
+                    for j in (i + 1)..members.len() {
+                        let from = members[i];
+                        let to = members[j];
+
+                        if let Some(path_result) = execute_a_star(&chunk_view, self.nodes[from].vertex, self.nodes[to].vertex, cost_func, options) {
+                            if let Some(last) = path_result.path.last() {
+                                self.edges.push(AbstractEdge { from, to, cost: last.cost });
+                                self.edges.push(AbstractEdge { from: to, to: from, cost: last.cost });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn chunk_of(&self, vertex: GraphVertex) -> (u32, u32) {
+        (vertex.x / self.chunk_size, vertex.y / self.chunk_size)
+    }
+
+    /// Connects `vertex` to every entrance already belonging to its chunk, so `start`/`goal`
+    /// (which usually don't sit exactly on an entrance) can take part in the abstract search.
+    fn connect_ad_hoc(&self, graph: &GridGraph, vertex: GraphVertex, cost_func: &dyn Fn(&GraphVertex, &GraphVertex, &Visited<GraphVertex>, &GraphVertex, f32, &PathfindingOptions) -> f32, options: &PathfindingOptions) -> Vec<AbstractEdge> {
+        let (chunk_x, chunk_y) = self.chunk_of(vertex);
+        let (origin_x, origin_y, width, height) = chunk_bounds(self.chunk_size, self.width, self.height, chunk_x, chunk_y);
+        let chunk_view = graph.sub_view(origin_x, origin_y, width, height);
+
+        let mut edges = Vec::new();
+
+        for (idx, node) in self.nodes.iter().enumerate() {
+            if node.chunk_x != chunk_x || node.chunk_y != chunk_y {
+                continue;
+            }
+
+            if let Some(path_result) = execute_a_star(&chunk_view, vertex, node.vertex, cost_func, options) {
+                if let Some(last) = path_result.path.last() {
+                    edges.push((idx, last.cost));
+                }
+            }
+        }
+
+        edges.into_iter().map(|(idx, cost)| AbstractEdge { from: self.nodes.len(), to: idx, cost }).collect()
+    }
+
+    /// Finds an approximate path from `start_vertex` to `goal_vertex` by routing through the
+    /// abstract chunk graph and refining only the chunks the abstract path actually crosses.
+    /// Returns the assembled path alongside an estimate of the low-level vertices this query
+    /// skipped: `chunk_size * chunk_size` for every chunk that no abstract-path segment starts or
+    /// ends in (partial chunks on the grid's edge are counted in full).
+    pub fn find_path(&self, graph: &GridGraph, start_vertex: GraphVertex, goal_vertex: GraphVertex, cost_func: &dyn Fn(&GraphVertex, &GraphVertex, &Visited<GraphVertex>, &GraphVertex, f32, &PathfindingOptions) -> f32, options: &PathfindingOptions) -> Option<(PathResult<GraphVertex>, u32)> {
+        let start_idx = self.nodes.len();
+        let goal_idx = self.nodes.len() + 1;
+
+        let mut extra_edges = self.connect_ad_hoc(graph, start_vertex, cost_func, options);
+        extra_edges.extend(self.connect_ad_hoc(graph, goal_vertex, cost_func, options).into_iter().map(|edge| AbstractEdge { from: goal_idx, to: edge.to, cost: edge.cost }));
+
+        // `start`/`goal` sharing a chunk with no boundary entrances between them (any grid no
+        // bigger than one chunk, or any short query confined to an interior chunk) would otherwise
+        // leave the abstract graph with no route at all; connect them directly via a local search.
+        let start_chunk = self.chunk_of(start_vertex);
+        let goal_chunk = self.chunk_of(goal_vertex);
+
+        if start_chunk == goal_chunk {
+            let (origin_x, origin_y, width, height) = chunk_bounds(self.chunk_size, self.width, self.height, start_chunk.0, start_chunk.1);
+            let chunk_view = graph.sub_view(origin_x, origin_y, width, height);
+
+            if let Some(path_result) = execute_a_star(&chunk_view, start_vertex, goal_vertex, cost_func, options) {
+                if let Some(last) = path_result.path.last() {
+                    extra_edges.push(AbstractEdge { from: start_idx, to: goal_idx, cost: last.cost });
+                }
+            }
+        }
+
+        let node_count = self.nodes.len() + 2;
+        let mut adjacency: Vec<Vec<AbstractEdge>> = vec![Vec::new(); node_count];
+
+        // self.edges already store both directions of every crossing/intra-chunk link
+        for edge in &self.edges {
+            adjacency[edge.from].push(*edge);
+        }
+
+        // extra_edges only point away from the ad-hoc start/goal nodes, so add their reverse too
+        for edge in &extra_edges {
+            adjacency[edge.from].push(*edge);
+            adjacency[edge.to].push(AbstractEdge { from: edge.to, to: edge.from, cost: edge.cost });
+        }
+
+        let node_vertex = |idx: usize| -> GraphVertex {
+            if idx == start_idx {
+                start_vertex
+            } else if idx == goal_idx {
+                goal_vertex
+            } else {
+                self.nodes[idx].vertex
+            }
+        };
+
+        let mut g_score = vec![f32::INFINITY; node_count];
+        let mut parent = vec![None; node_count];
+        let mut open_list = BinaryHeap::new();
+
+        g_score[start_idx] = 0.;
+        open_list.push(AbstractVisit { node: start_idx, cost: euclidean_heuristic(&start_vertex, &goal_vertex) * options.heuristics_weight });
+
+        let mut reached_goal = false;
+
+        while let Some(current) = open_list.pop() {
+            if current.node == goal_idx {
+                reached_goal = true;
+                break;
+            }
+
+            for edge in &adjacency[current.node] {
+                let tentative_g = g_score[current.node] + edge.cost * options.cost_weight;
+
+                if tentative_g < g_score[edge.to] {
+                    g_score[edge.to] = tentative_g;
+                    parent[edge.to] = Some(current.node);
+
+                    let h = euclidean_heuristic(&node_vertex(edge.to), &goal_vertex) * options.heuristics_weight;
+                    open_list.push(AbstractVisit { node: edge.to, cost: tentative_g + h });
+                }
+            }
+        }
+
+        if !reached_goal {
+            return None;
+        }
+
+        let mut abstract_path = vec![goal_idx];
+        let mut node = goal_idx;
+
+        while let Some(prev) = parent[node] {
+            abstract_path.push(prev);
+            node = prev;
+        }
+
+        abstract_path.reverse();
+
+        let mut visited_vertices = Vec::new();
+        let mut path_vertices: Vec<Visited<GraphVertex>> = Vec::new();
+        let mut refined_chunks = std::collections::HashSet::new();
+
+        for window in abstract_path.windows(2) {
+            let from_vertex = node_vertex(window[0]);
+            let to_vertex = node_vertex(window[1]);
+
+            let from_chunk = self.chunk_of(from_vertex);
+            let to_chunk = self.chunk_of(to_vertex);
+            refined_chunks.insert(from_chunk);
+            refined_chunks.insert(to_chunk);
+
+            let (origin_x, origin_y, width, height) = chunks_bounds(self.chunk_size, self.width, self.height, from_chunk, to_chunk);
+            let segment_view = graph.sub_view(origin_x, origin_y, width, height);
+
+            let segment = execute_a_star(&segment_view, from_vertex, to_vertex, cost_func, options)?;
+
+            visited_vertices.extend(segment.visited_vertices);
+
+            // `segment.path` always starts with `from_vertex` (it's the search's own start
+            // vertex), which is already the last vertex of the previous segment -- skip it to
+            // avoid adding every entrance twice.
+            if path_vertices.is_empty() {
+                path_vertices.extend(segment.path);
+            } else {
+                path_vertices.extend(segment.path.into_iter().skip(1));
+            }
+        }
+
+        let total_chunks = (self.chunks_x * self.chunks_y) as usize;
+        let avoided_vertices = ((total_chunks - refined_chunks.len().min(total_chunks)) as u32) * self.chunk_size * self.chunk_size;
+
+        Some((PathResult { path: path_vertices, visited_vertices }, avoided_vertices))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid_graph::CARDINAL_DIRECTIONS;
+
+    /// A fully open grid whose boundaries are long, unobstructed walkable runs spanning several
+    /// chunk-rows/columns -- `scan_boundary` must emit an entrance per chunk the run passes
+    /// through, not just one at the run's midpoint, or interior chunks end up with no entrances
+    /// and `find_path` can't route through them at all.
+    #[test]
+    fn find_path_succeeds_on_a_fully_open_grid_with_long_boundaries() {
+        let width = 8_u32;
+        let height = 8_u32;
+        let columns: Vec<[u8; 8]> = (0..width).map(|_| [1; 8]).collect();
+        let tiles: Vec<&[u8]> = columns.iter().map(|column| column.as_slice()).collect();
+
+        let graph = GridGraph::new(width, height, &tiles, &CARDINAL_DIRECTIONS);
+        let options = PathfindingOptions {
+            cost_weight: 1.,
+            heuristics_weight: 1.,
+            min_run: 0,
+            max_run: u32::MAX,
+            beam_width: None,
+        };
+
+        let cache = PathCache::build(&graph, 4, &crate::zero_cost_function, &options);
+
+        let start = GraphVertex::new(0, 0);
+        let goal = GraphVertex::new(7, 7);
+
+        let (result, _avoided_vertices) = cache.find_path(&graph, start, goal, &crate::zero_cost_function, &options)
+            .expect("a path should exist between two corners of a fully open grid");
+
+        let path_nodes: Vec<GraphVertex> = result.path.iter().map(|visited| visited.node).collect();
+        assert_eq!(path_nodes.first(), Some(&start));
+        assert_eq!(path_nodes.last(), Some(&goal));
+
+        let mut deduped_nodes = path_nodes.clone();
+        deduped_nodes.dedup();
+        assert_eq!(path_nodes, deduped_nodes, "assembled path must not contain back-to-back duplicate vertices");
+    }
+}
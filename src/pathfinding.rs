@@ -1,77 +1,245 @@
+use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap};
 use crate::{Args, PathfindingOptions};
 
-use crate::grid_graph::{ALL_DIRECTIONS, GraphVertex, GridGraph, VisitedGraphVertex};
+use crate::d_ary_heap::DAryHeap;
+use crate::graph::{Graph, Visited};
+use crate::grid_graph::{Direction, GraphVertex, GridGraph};
 
 #[derive(Debug)]
-pub struct PathResult {
-    pub path: Vec<VisitedGraphVertex>,
-    pub visited_vertices: Vec<VisitedGraphVertex>,
+pub struct PathResult<N> {
+    pub path: Vec<Visited<N>>,
+    pub visited_vertices: Vec<Visited<N>>,
 }
 
-// cost_func(start_vertex, goal_vertex, last_visited_vertex, current_vertex_cost)
-pub fn execute_a_star(graph: &GridGraph, start_vertex: GraphVertex, goal_vertex: GraphVertex, cost_func: &dyn Fn(&GraphVertex, &GraphVertex, &VisitedGraphVertex, &GraphVertex, u8, &PathfindingOptions) -> f32, options: &PathfindingOptions) -> Option<PathResult> {
-    let mut open_list: BinaryHeap<VisitedGraphVertex> = BinaryHeap::new();
-    let mut closed_list: HashMap<GraphVertex, f32> = HashMap::new();
-    let mut parent_map: HashMap<GraphVertex, VisitedGraphVertex> = HashMap::new();
+// cost_func(start_vertex, goal_vertex, last_visited_vertex, current_vertex, current_vertex_cost)
+pub fn execute_a_star<G: Graph>(graph: &G, start_vertex: G::Node, goal_vertex: G::Node, cost_func: &dyn Fn(&G::Node, &G::Node, &Visited<G::Node>, &G::Node, f32, &PathfindingOptions) -> f32, options: &PathfindingOptions) -> Option<PathResult<G::Node>> {
+    let mut open_list: DAryHeap<Visited<G::Node>> = DAryHeap::new();
+    let mut g_score: HashMap<G::Node, f32> = HashMap::new();
+    let mut f_score: HashMap<G::Node, f32> = HashMap::new();
+    let mut parent_map: HashMap<G::Node, G::Node> = HashMap::new();
 
     let mut visited_vertices = Vec::new();
 
-    open_list.push(start_vertex.into_visited(0.));
-    closed_list.insert(start_vertex, 0.);
+    g_score.insert(start_vertex, 0.);
+    f_score.insert(start_vertex, 0.);
+    open_list.push(Visited::new(start_vertex, 0.));
 
     let mut visit_amount = 0;
     let mut visit_neighbour_amount = 0;
 
     while let Some(current_vertex) = open_list.pop() {
+        // Lazy deletion (see Visited's doc comment)
+        if current_vertex.cost > *f_score.get(&current_vertex.node).unwrap_or(&f32::INFINITY) {
+            continue;
+        }
+
         visit_amount += 1;
         visited_vertices.push(current_vertex);
 
         if current_vertex == goal_vertex {
-            println!("Found goal {:?}", current_vertex);
+            println!("Found goal {:?}", current_vertex.node);
 
             break;
         }
 
         let mut curr_visited_neighbours = 0;
+        let current_g = *g_score.get(&current_vertex.node).unwrap();
 
-        for direction in ALL_DIRECTIONS {
-            if let Some(neighbour_vertex) = graph.get_neighbouring_vertex(&current_vertex, direction) {
-                if let std::collections::hash_map::Entry::Vacant(new_entry) = closed_list.entry(neighbour_vertex) {
-                    visit_neighbour_amount += 1;
-                    curr_visited_neighbours += 1;
-                    let vertex_cost = graph.get_cost(&neighbour_vertex);
-                    let calculated_cost: f32 = cost_func(&start_vertex, &goal_vertex, &current_vertex, &neighbour_vertex, vertex_cost, options);
+        for neighbour_vertex in graph.neighbours(&current_vertex.node) {
+            visit_neighbour_amount += 1;
 
-                    let visited_neighbour_vertex = neighbour_vertex.into_visited(calculated_cost);
-                    //println!("{}, {} to {}, {} cost: {} + {} => {}", current_vertex.x, current_vertex.y, neighbour_vertex.x, neighbour_vertex.y, current_vertex.cost, vertex_cost, calculated_cost);
+            let vertex_cost = graph.cost(&current_vertex.node, &neighbour_vertex);
+            let tentative_g = current_g + vertex_cost;
 
-                    if !parent_map.contains_key(&neighbour_vertex) || parent_map.get(&neighbour_vertex).unwrap().cost > current_vertex.cost {
-                        parent_map.insert(neighbour_vertex, current_vertex);
-                    }
-                    open_list.push(visited_neighbour_vertex);
-                    new_entry.insert(current_vertex.cost);
+            if tentative_g < *g_score.get(&neighbour_vertex).unwrap_or(&f32::INFINITY) {
+                curr_visited_neighbours += 1;
+                let last_visited = Visited::new(current_vertex.node, current_g);
+                let calculated_cost: f32 = cost_func(&start_vertex, &goal_vertex, &last_visited, &neighbour_vertex, vertex_cost, options);
 
-                    println!("{}, {} visited {}, {}", current_vertex.x, current_vertex.y, neighbour_vertex.x, neighbour_vertex.y);
-                }
+                g_score.insert(neighbour_vertex, tentative_g);
+                f_score.insert(neighbour_vertex, calculated_cost);
+                parent_map.insert(neighbour_vertex, current_vertex.node);
+                open_list.push(Visited::new(neighbour_vertex, calculated_cost));
+
+                println!("{:?} visited {:?}", current_vertex.node, neighbour_vertex);
             }
         }
 
-        println!("{}, {} visited {} neighbours", current_vertex.x, current_vertex.y, curr_visited_neighbours);
+        println!("{:?} visited {} neighbours", current_vertex.node, curr_visited_neighbours);
+
+        // Beam search: bound the open list to the `beam_width` lowest-f-cost entries so memory
+        // stays flat on huge grids. This trades the optimality guarantee for bounded memory.
+        if let Some(beam_width) = options.beam_width {
+            if open_list.len() > beam_width {
+                let mut buffer: Vec<Visited<G::Node>> = open_list.drain().collect();
+                buffer.sort_by(|a, b| a.cost.partial_cmp(&b.cost).unwrap_or(Ordering::Equal));
+                buffer.truncate(beam_width);
+                open_list = buffer.into_iter().collect();
+
+                println!("Beam pruned the open list down to {} entries; the resulting path may no longer be optimal", beam_width);
+            }
+        }
     }
 
     println!("Visited {} vertices and {} neighbours", visit_amount, visit_neighbour_amount);
 
-    let mut path_vertices: Vec<VisitedGraphVertex> = Vec::new();
+    g_score.get(&goal_vertex)?;
+
+    let mut path_vertices: Vec<Visited<G::Node>> = Vec::new();
+    let mut vertex = goal_vertex;
+
+    loop {
+        path_vertices.push(Visited::new(vertex, *g_score.get(&vertex).unwrap()));
+
+        match parent_map.get(&vertex) {
+            Some(&parent) => vertex = parent,
+            None => break,
+        }
+    }
+
+    path_vertices.reverse();
+
+    Some(PathResult {
+        visited_vertices,
+        path: path_vertices,
+    })
+}
+
+/// A search-state for movement-constrained ("crucible") pathfinding: since the same tile can be
+/// optimally reached with different run lengths/directions, the state has to carry those along
+/// instead of collapsing to just a [`GraphVertex`].
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct ConstrainedState {
+    pub vertex: GraphVertex,
+    pub incoming_direction: Option<Direction>,
+    pub run_length: u32,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct VisitedState {
+    state: ConstrainedState,
+    cost: f32,
+}
+
+impl PartialEq for VisitedState {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for VisitedState {}
+
+impl PartialOrd for VisitedState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.cost.partial_cmp(&other.cost).map(|cmp| cmp.reverse())
+    }
+}
+
+impl Ord for VisitedState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// "Crucible" variant of [`execute_a_star`]: a path may take at most `options.max_run` consecutive
+/// steps in the same direction and must take at least `options.min_run` steps before it is allowed
+/// to turn (or reach the goal). The search state is expanded to `(vertex, incoming_direction,
+/// run_length)` since the same tile can be optimal under different movement states, and g-scores
+/// are properly relaxed (with re-expansion via lazy deletion) so the result stays optimal. This
+/// mode is specific to `GridGraph`, whose fixed, enumerable `Direction`s the run-length rule needs.
+pub fn execute_a_star_constrained(graph: &GridGraph, start_vertex: GraphVertex, goal_vertex: GraphVertex, cost_func: &dyn Fn(&GraphVertex, &GraphVertex, &Visited<GraphVertex>, &GraphVertex, f32, &PathfindingOptions) -> f32, options: &PathfindingOptions) -> Option<PathResult<GraphVertex>> {
+    let mut open_list: BinaryHeap<VisitedState> = BinaryHeap::new();
+    let mut g_score: HashMap<ConstrainedState, f32> = HashMap::new();
+    let mut f_score: HashMap<ConstrainedState, f32> = HashMap::new();
+    let mut parent_map: HashMap<ConstrainedState, ConstrainedState> = HashMap::new();
+
+    let mut visited_vertices = Vec::new();
+
+    let start_state = ConstrainedState {
+        vertex: start_vertex,
+        incoming_direction: None,
+        run_length: 0,
+    };
+
+    g_score.insert(start_state, 0.);
+    f_score.insert(start_state, 0.);
+    open_list.push(VisitedState { state: start_state, cost: 0. });
+
+    let mut goal_state = None;
+
+    while let Some(current) = open_list.pop() {
+        // Lazy deletion (see Visited's doc comment)
+        if current.cost > *f_score.get(&current.state).unwrap_or(&f32::INFINITY) {
+            continue;
+        }
+
+        visited_vertices.push(current.state.vertex.into_visited(current.cost));
+
+        if current.state.vertex == goal_vertex && current.state.run_length >= options.min_run {
+            goal_state = Some(current.state);
+
+            break;
+        }
+
+        for direction in graph.directions() {
+            if let Some(incoming) = current.state.incoming_direction {
+                if direction.is_opposite(&incoming) {
+                    continue;
+                }
+            }
+
+            let is_straight = current.state.incoming_direction.map_or(true, |incoming| incoming.is_same(direction));
+
+            if is_straight {
+                if current.state.run_length >= options.max_run {
+                    continue;
+                }
+            } else if current.state.incoming_direction.is_some() && current.state.run_length < options.min_run {
+                continue;
+            }
+
+            if let Some(neighbour_vertex) = graph.get_neighbouring_vertex(&current.state.vertex, direction) {
+                if !graph.is_walkable(&neighbour_vertex) {
+                    continue;
+                }
+
+                let next_run_length = if is_straight { current.state.run_length + 1 } else { 1 };
+                let next_state = ConstrainedState {
+                    vertex: neighbour_vertex,
+                    incoming_direction: Some(*direction),
+                    run_length: next_run_length,
+                };
+
+                let vertex_cost = graph.get_cost(&neighbour_vertex) as f32 * direction.cost_multiplier();
+                let last_g = *g_score.get(&current.state).unwrap();
+                let tentative_g = last_g + vertex_cost;
+
+                if tentative_g < *g_score.get(&next_state).unwrap_or(&f32::INFINITY) {
+                    let last_visited = current.state.vertex.into_visited(last_g);
+                    let f = cost_func(&start_vertex, &goal_vertex, &last_visited, &neighbour_vertex, vertex_cost, options);
+
+                    g_score.insert(next_state, tentative_g);
+                    f_score.insert(next_state, f);
+                    parent_map.insert(next_state, current.state);
+                    open_list.push(VisitedState { state: next_state, cost: f });
+                }
+            }
+        }
+    }
+
+    let goal_state = goal_state?;
 
-    println!("{}", parent_map.contains_key(&goal_vertex));
-    let mut vertex = *parent_map.get(&goal_vertex)?;
+    let mut path_vertices: Vec<Visited<GraphVertex>> = Vec::new();
+    let mut state = goal_state;
 
-    path_vertices.push(vertex);
+    loop {
+        path_vertices.push(state.vertex.into_visited(*g_score.get(&state).unwrap()));
 
-    while parent_map.contains_key(&vertex.into()) {
-        vertex = *parent_map.get(&vertex.into()).unwrap();
-        path_vertices.push(vertex);
+        match parent_map.get(&state) {
+            Some(&parent) => state = parent,
+            None => break,
+        }
     }
 
     path_vertices.reverse();
@@ -80,4 +248,47 @@ pub fn execute_a_star(graph: &GridGraph, start_vertex: GraphVertex, goal_vertex:
         visited_vertices,
         path: path_vertices,
     })
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid_graph::CARDINAL_DIRECTIONS;
+    use crate::PathfindingOptions;
+
+    /// A node reachable by a cheap direct route that's costed higher per-tile, and a cheaper
+    /// longer route around it, regression-tests the g-score relaxation (and matching parent-map
+    /// update) that `execute_a_star` needs to settle on the actually-optimal path and not just
+    /// whichever one happened to reach a node first.
+    #[test]
+    fn execute_a_star_settles_on_the_cheaper_route_found_later() {
+        let column_0: [u8; 2] = [1, 1];
+        let column_1: [u8; 2] = [10, 1];
+        let column_2: [u8; 2] = [1, 1];
+        let tiles: [&[u8]; 3] = [&column_0, &column_1, &column_2];
+
+        let graph = GridGraph::new(3, 2, &tiles, &CARDINAL_DIRECTIONS);
+        let options = PathfindingOptions {
+            cost_weight: 1.,
+            heuristics_weight: 1.,
+            min_run: 0,
+            max_run: u32::MAX,
+            beam_width: None,
+        };
+
+        let start = GraphVertex::new(0, 0);
+        let goal = GraphVertex::new(2, 0);
+
+        let result = execute_a_star(&graph, start, goal, &crate::zero_cost_function, &options).expect("a path should exist");
+
+        let path_nodes: Vec<GraphVertex> = result.path.iter().map(|visited| visited.node).collect();
+        assert_eq!(path_nodes, vec![
+            GraphVertex::new(0, 0),
+            GraphVertex::new(0, 1),
+            GraphVertex::new(1, 1),
+            GraphVertex::new(2, 1),
+            GraphVertex::new(2, 0),
+        ]);
+        assert_eq!(result.path.last().unwrap().cost, 4.);
+    }
+}
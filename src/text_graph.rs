@@ -0,0 +1,77 @@
+use crate::graph::Graph;
+use crate::grid_graph::GraphVertex;
+
+/// Marks the start vertex when it appears in place of a matrix entry.
+pub const START_SENTINEL: &str = "S";
+/// Marks the goal vertex when it appears in place of a matrix entry.
+pub const GOAL_SENTINEL: &str = "G";
+
+/// A plain-text adjacency/cost matrix: `matrix[i][j]` is the cost of moving from vertex `i` to
+/// vertex `j`, or `None` if there is no edge. Lets [`crate::pathfinding::execute_a_star`] search
+/// arbitrary weighted graphs that aren't backed by an image. Reuses [`GraphVertex`] (with `y`
+/// fixed to `0`, vertex index in `x`) as the node type so the existing cost-function signature
+/// fits unchanged. The distance heuristics would read that index as a position, so only the
+/// zero-cost function is meaningful for this graph.
+pub struct TextGraph {
+    matrix: Vec<Vec<Option<f32>>>,
+}
+
+impl TextGraph {
+    /// Parses whitespace-separated rows of non-negative integers into a cost matrix. The diagonal
+    /// entry of the start/goal vertex is written as `S`/`G` instead of a number; every other `0`
+    /// means "no edge" and any other value is that edge's cost.
+    pub fn parse(input: &str) -> (Self, GraphVertex, GraphVertex) {
+        let mut start = None;
+        let mut goal = None;
+
+        let matrix: Vec<Vec<Option<f32>>> = input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(row, line)| {
+                line.split_whitespace()
+                    .enumerate()
+                    .map(|(col, token)| {
+                        if token == START_SENTINEL {
+                            assert_eq!(col, row, "S must be on the matrix diagonal, found at row {}, col {}", row, col);
+                            start = Some(GraphVertex::new(row as u32, 0));
+                            None
+                        } else if token == GOAL_SENTINEL {
+                            assert_eq!(col, row, "G must be on the matrix diagonal, found at row {}, col {}", row, col);
+                            goal = Some(GraphVertex::new(row as u32, 0));
+                            None
+                        } else {
+                            let value: f32 = token.parse().unwrap_or_else(|_| panic!("Invalid entry '{}' at row {}, col {} of text graph matrix", token, row, col));
+
+                            if value == 0. { None } else { Some(value) }
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let start = start.expect("No start vertex (S) found on the matrix diagonal");
+        let goal = goal.expect("No goal vertex (G) found on the matrix diagonal");
+
+        (Self { matrix }, start, goal)
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.matrix.len()
+    }
+}
+
+impl Graph for TextGraph {
+    type Node = GraphVertex;
+
+    fn neighbours(&self, node: &GraphVertex) -> impl Iterator<Item = GraphVertex> {
+        let row = node.x as usize;
+        let vertex_count = self.matrix.len();
+
+        (0..vertex_count).filter_map(move |col| self.matrix[row][col].map(|_| GraphVertex::new(col as u32, 0)))
+    }
+
+    fn cost(&self, from: &GraphVertex, to: &GraphVertex) -> f32 {
+        self.matrix[from.x as usize][to.x as usize].unwrap_or(0.)
+    }
+}